@@ -0,0 +1,18 @@
+use std::sync::Arc;
+
+use axum::{extract::State, routing::get, Json, Router};
+
+use crate::state::AppState;
+
+pub async fn serve(state: Arc<AppState>) {
+    let app = Router::new()
+        .route("/controllers", get(list_controllers))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+    axum::serve(listener, app).await.unwrap();
+}
+
+async fn list_controllers(State(state): State<Arc<AppState>>) -> Json<Vec<String>> {
+    Json(state.controller_names())
+}
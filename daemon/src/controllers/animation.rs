@@ -0,0 +1,245 @@
+use std::time::Duration;
+
+use super::RgbColor;
+
+/// A reusable LED effect. `frame` is pure with respect to `t` (time since the
+/// animation was registered) so the scheduler can tick it at whatever rate it
+/// likes without the animation keeping its own clock.
+pub trait Animation: Send {
+    fn frame(&mut self, t: Duration) -> Vec<(u8, u8, RgbColor)>;
+
+    /// Whether the animation has run its course and can be dropped.
+    /// Looping animations (pulse, spinner, wave) simply never finish.
+    fn finished(&self) -> bool;
+}
+
+fn scale(color: RgbColor, factor: f32) -> RgbColor {
+    let (r, g, b) = color;
+    let scale = |c: u8| (c as f32 * factor).round().clamp(0.0, 255.0) as u8;
+    (scale(r), scale(g), scale(b))
+}
+
+/// Breathes a single button's brightness in and out around a base color.
+pub struct Pulse {
+    pub x: u8,
+    pub y: u8,
+    pub base: RgbColor,
+    pub period: Duration,
+}
+
+impl Animation for Pulse {
+    fn frame(&mut self, t: Duration) -> Vec<(u8, u8, RgbColor)> {
+        let phase = (t.as_secs_f32() / self.period.as_secs_f32()) * std::f32::consts::TAU;
+        let brightness = (phase.sin() + 1.0) / 2.0;
+        vec![(self.x, self.y, scale(self.base, brightness))]
+    }
+
+    fn finished(&self) -> bool {
+        false
+    }
+}
+
+/// Rotates a single lit button around the perimeter of an 8x8 grid.
+pub struct Spinner {
+    pub base: RgbColor,
+    pub period: Duration,
+}
+
+impl Spinner {
+    /// Coordinates of the 28 buttons that make up the outer ring of an 8x8 grid.
+    fn ring() -> Vec<(u8, u8)> {
+        let mut ring = Vec::new();
+        for x in 0..8 {
+            ring.push((x, 0));
+        }
+        for y in 1..7 {
+            ring.push((7, y));
+        }
+        for x in (0..8).rev() {
+            ring.push((x, 7));
+        }
+        for y in (1..7).rev() {
+            ring.push((0, y));
+        }
+        ring
+    }
+}
+
+impl Animation for Spinner {
+    fn frame(&mut self, t: Duration) -> Vec<(u8, u8, RgbColor)> {
+        let ring = Self::ring();
+        let progress = (t.as_secs_f32() / self.period.as_secs_f32()).fract();
+        let head = ((progress * ring.len() as f32) as usize) % ring.len();
+        let (x, y) = ring[head];
+        vec![(x, y, self.base)]
+    }
+
+    fn finished(&self) -> bool {
+        false
+    }
+}
+
+/// Sweeps a brightness gradient across the columns of the grid.
+pub struct Wave {
+    pub width: u8,
+    pub height: u8,
+    pub base: RgbColor,
+    pub period: Duration,
+}
+
+impl Animation for Wave {
+    fn frame(&mut self, t: Duration) -> Vec<(u8, u8, RgbColor)> {
+        let progress = (t.as_secs_f32() / self.period.as_secs_f32()).fract();
+        let head = progress * self.width as f32;
+
+        let mut frame = Vec::with_capacity(self.width as usize * self.height as usize);
+        for x in 0..self.width {
+            let distance = (x as f32 - head).abs().min(self.width as f32 - (x as f32 - head).abs());
+            let brightness = (1.0 - distance / (self.width as f32 / 2.0)).max(0.0);
+            let color = scale(self.base, brightness);
+            for y in 0..self.height {
+                frame.push((x, y, color));
+            }
+        }
+        frame
+    }
+
+    fn finished(&self) -> bool {
+        false
+    }
+}
+
+/// Fills the first `filled` of `buttons` with `base` and turns the rest off.
+/// A one-shot animation: it renders a single frame and immediately reports
+/// `finished`, letting callers drive a progress bar by re-registering it with
+/// an updated `filled` count.
+pub struct LinearProgress {
+    pub buttons: Vec<(u8, u8)>,
+    pub filled: usize,
+    pub base: RgbColor,
+}
+
+impl Animation for LinearProgress {
+    fn frame(&mut self, _t: Duration) -> Vec<(u8, u8, RgbColor)> {
+        self.buttons
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, y))| {
+                let color = if i < self.filled { self.base } else { (0, 0, 0) };
+                (x, y, color)
+            })
+            .collect()
+    }
+
+    fn finished(&self) -> bool {
+        true
+    }
+}
+
+/// Ticks every active animation for a controller at a fixed rate and merges
+/// their frames into a single batch of `set_button_color_multi` updates.
+pub struct AnimationScheduler {
+    tick_rate: Duration,
+    animations: Vec<(Box<dyn Animation>, std::time::Instant)>,
+}
+
+impl AnimationScheduler {
+    pub fn new(tick_rate: Duration) -> Self {
+        Self {
+            tick_rate,
+            animations: Vec::new(),
+        }
+    }
+
+    pub fn register(&mut self, animation: Box<dyn Animation>) {
+        self.animations.push((animation, std::time::Instant::now()));
+    }
+
+    /// Drives every registered animation in a loop, pushing merged frames
+    /// through `render` until there is nothing left to animate.
+    pub async fn run(&mut self, mut render: impl FnMut(&[(u8, u8, RgbColor)])) {
+        let mut interval = tokio::time::interval(self.tick_rate);
+
+        while !self.animations.is_empty() {
+            interval.tick().await;
+
+            let mut merged = std::collections::HashMap::new();
+            for (animation, started_at) in &mut self.animations {
+                for (x, y, color) in animation.frame(started_at.elapsed()) {
+                    merged.insert((x, y), color);
+                }
+            }
+
+            self.animations.retain(|(animation, _)| !animation.finished());
+
+            let updates: Vec<(u8, u8, RgbColor)> =
+                merged.into_iter().map(|((x, y), color)| (x, y, color)).collect();
+            render(&updates);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pulse_breathes_full_brightness_at_a_quarter_period_and_off_at_three_quarters() {
+        let mut pulse = Pulse {
+            x: 2,
+            y: 3,
+            base: (100, 100, 100),
+            period: Duration::from_secs(4),
+        };
+
+        assert_eq!(pulse.frame(Duration::ZERO), vec![(2, 3, (50, 50, 50))]);
+        assert_eq!(
+            pulse.frame(Duration::from_secs(1)),
+            vec![(2, 3, (100, 100, 100))]
+        );
+        assert_eq!(pulse.frame(Duration::from_secs(3)), vec![(2, 3, (0, 0, 0))]);
+    }
+
+    #[test]
+    fn spinner_wraps_around_the_ring_once_per_period() {
+        let mut spinner = Spinner {
+            base: (255, 0, 0),
+            period: Duration::from_secs(1),
+        };
+
+        assert_eq!(spinner.frame(Duration::ZERO), vec![(0, 0, (255, 0, 0))]);
+        let wrapped = spinner.frame(Duration::from_secs(1));
+        assert_eq!(wrapped, spinner.frame(Duration::ZERO));
+    }
+
+    #[test]
+    fn wave_peaks_under_the_head_and_fades_with_distance() {
+        let mut wave = Wave {
+            width: 4,
+            height: 1,
+            base: (200, 0, 0),
+            period: Duration::from_secs(4),
+        };
+
+        // At t=0 the head sits on column 0, so that column is full brightness
+        // and the opposite column (2, half the width away) is fully dark.
+        let frame = wave.frame(Duration::ZERO);
+        assert_eq!(frame[0], (0, 0, (200, 0, 0)));
+        assert_eq!(frame[2], (2, 0, (0, 0, 0)));
+    }
+
+    #[test]
+    fn linear_progress_lights_only_the_filled_prefix_and_finishes_immediately() {
+        let mut progress = LinearProgress {
+            buttons: vec![(0, 0), (1, 0), (2, 0)],
+            filled: 2,
+            base: (0, 0, 255),
+        };
+
+        assert_eq!(
+            progress.frame(Duration::ZERO),
+            vec![(0, 0, (0, 0, 255)), (1, 0, (0, 0, 255)), (2, 0, (0, 0, 0))]
+        );
+        assert!(progress.finished());
+    }
+}
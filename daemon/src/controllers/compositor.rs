@@ -0,0 +1,245 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use tokio::{sync::mpsc, time::Instant};
+use tracing::warn;
+
+use super::{Controller, RgbColor};
+
+/// Minimum time between two renders. Actions arriving faster than this are
+/// coalesced into a single render at the deadline instead of flushing every
+/// event, keeping output to roughly 60fps.
+const RENDER_COOLDOWN: Duration = Duration::from_millis(16);
+
+pub type Frame = HashMap<(u8, u8), RgbColor>;
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    ReplaceAll(Frame),
+    ReplaceLayer { index: usize, frame: Frame },
+    ClearLayer(usize),
+}
+
+/// Ordered stack of sparse layers. Layer 0 is at the bottom; higher indices
+/// paint over it. A cell missing from a layer's map is transparent there.
+#[derive(Default)]
+struct LayerStack {
+    layers: Vec<Frame>,
+}
+
+impl LayerStack {
+    fn layer_mut(&mut self, index: usize) -> &mut Frame {
+        if index >= self.layers.len() {
+            self.layers.resize_with(index + 1, Frame::default);
+        }
+        &mut self.layers[index]
+    }
+
+    /// Flattens the stack top-down: the topmost opaque cell at each position wins.
+    fn flatten(&self) -> Frame {
+        let mut flattened = Frame::new();
+        for layer in &self.layers {
+            for (&pos, &color) in layer {
+                flattened.insert(pos, color);
+            }
+        }
+        flattened
+    }
+}
+
+/// Sits between scripts and a `Controller`, letting several scripts share one
+/// grid by submitting layers over a channel instead of writing buttons directly.
+pub struct Compositor {
+    controller: Arc<Box<dyn Controller>>,
+    actions: mpsc::Receiver<Action>,
+}
+
+impl Compositor {
+    pub fn new(controller: Arc<Box<dyn Controller>>) -> (Self, mpsc::Sender<Action>) {
+        let (sender, actions) = mpsc::channel(64);
+        (
+            Self {
+                controller,
+                actions,
+            },
+            sender,
+        )
+    }
+
+    /// Drives the compositor until its action channel closes, rendering
+    /// at most once per `RENDER_COOLDOWN`.
+    pub async fn run(mut self) {
+        let mut stack = LayerStack::default();
+        let mut last_rendered = Frame::new();
+        let mut last_render_at: Option<Instant> = None;
+        let mut pending = false;
+
+        loop {
+            let action = match last_render_at {
+                Some(at) if pending && at.elapsed() < RENDER_COOLDOWN => {
+                    tokio::select! {
+                        action = self.actions.recv() => action,
+                        _ = tokio::time::sleep_until(at + RENDER_COOLDOWN) => {
+                            self.render(&stack, &mut last_rendered);
+                            last_render_at = Some(Instant::now());
+                            pending = false;
+                            continue;
+                        }
+                    }
+                }
+                _ => self.actions.recv().await,
+            };
+
+            let Some(action) = action else {
+                break;
+            };
+
+            match action {
+                Action::ReplaceAll(frame) => stack.layers = vec![frame],
+                Action::ReplaceLayer { index, frame } => *stack.layer_mut(index) = frame,
+                Action::ClearLayer(index) => stack.layer_mut(index).clear(),
+            }
+
+            let can_render_now = last_render_at
+                .map(|at| at.elapsed() >= RENDER_COOLDOWN)
+                .unwrap_or(true);
+
+            if can_render_now {
+                self.render(&stack, &mut last_rendered);
+                last_render_at = Some(Instant::now());
+                pending = false;
+            } else {
+                pending = true;
+            }
+        }
+    }
+
+    /// Diffs the flattened stack against the last frame we sent and only
+    /// pushes the buttons that actually changed.
+    fn render(&self, stack: &LayerStack, last_rendered: &mut Frame) {
+        let flattened = stack.flatten();
+
+        let mut updates: Vec<(u8, u8, RgbColor)> = flattened
+            .iter()
+            .filter(|(pos, color)| last_rendered.get(pos) != Some(*color))
+            .map(|(&(x, y), &color)| (x, y, color))
+            .collect();
+
+        // Cells lit in the previous frame but absent from this one (e.g. after
+        // `ClearLayer`) are transparent now, not "unchanged" — turn them off.
+        updates.extend(
+            last_rendered
+                .keys()
+                .filter(|pos| !flattened.contains_key(pos))
+                .map(|&(x, y)| (x, y, (0, 0, 0))),
+        );
+
+        if !updates.is_empty() {
+            if let Err(error) = self.controller.set_button_color_multi(&updates) {
+                warn!("Compositor render failed: {:?}", error);
+            }
+        }
+
+        *last_rendered = flattened;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use launchy::MidiError;
+
+    use super::*;
+    use crate::controllers::ControllerEvent;
+
+    /// A `Controller` that just records every batch it's asked to render,
+    /// so tests can assert on the diff `Compositor::render` computed.
+    struct RecordingController {
+        calls: Arc<Mutex<Vec<Vec<(u8, u8, RgbColor)>>>>,
+    }
+
+    impl Controller for RecordingController {
+        fn guess() -> Result<Box<Self>, MidiError> {
+            unimplemented!()
+        }
+
+        fn guess_ok() -> Result<(), MidiError> {
+            unimplemented!()
+        }
+
+        fn initialize(&self) -> Result<(), MidiError> {
+            Ok(())
+        }
+
+        fn clear(&self) -> Result<(), MidiError> {
+            Ok(())
+        }
+
+        fn get_event_receiver(
+            &self,
+        ) -> Result<tokio::sync::broadcast::Receiver<ControllerEvent>, ()> {
+            Err(())
+        }
+
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        fn set_button_color(&self, x: u8, y: u8, color: RgbColor) -> Result<(), MidiError> {
+            self.set_button_color_multi(&[(x, y, color)])
+        }
+
+        fn set_button_color_multi(&self, updates: &[(u8, u8, RgbColor)]) -> Result<(), MidiError> {
+            self.calls.lock().unwrap().push(updates.to_vec());
+            Ok(())
+        }
+    }
+
+    fn sorted(mut updates: Vec<(u8, u8, RgbColor)>) -> Vec<(u8, u8, RgbColor)> {
+        updates.sort();
+        updates
+    }
+
+    #[test]
+    fn render_only_pushes_cells_that_changed() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let controller: Arc<Box<dyn Controller>> =
+            Arc::new(Box::new(RecordingController { calls: calls.clone() }));
+        let (compositor, _actions) = Compositor::new(controller);
+
+        let mut stack = LayerStack::default();
+        *stack.layer_mut(0) = [((0, 0), (255, 0, 0)), ((1, 0), (0, 255, 0))]
+            .into_iter()
+            .collect();
+        let mut last_rendered = Frame::new();
+
+        compositor.render(&stack, &mut last_rendered);
+        assert_eq!(
+            sorted(calls.lock().unwrap().remove(0)),
+            sorted(vec![(0, 0, (255, 0, 0)), (1, 0, (0, 255, 0))])
+        );
+
+        // Re-rendering the identical stack is a no-op: nothing changed.
+        compositor.render(&stack, &mut last_rendered);
+        assert!(calls.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn render_turns_off_cells_dropped_from_the_new_frame() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let controller: Arc<Box<dyn Controller>> =
+            Arc::new(Box::new(RecordingController { calls: calls.clone() }));
+        let (compositor, _actions) = Compositor::new(controller);
+
+        let mut stack = LayerStack::default();
+        *stack.layer_mut(0) = [((0, 0), (255, 0, 0))].into_iter().collect();
+        let mut last_rendered = Frame::new();
+        compositor.render(&stack, &mut last_rendered);
+        calls.lock().unwrap().clear();
+
+        stack.layer_mut(0).clear();
+        compositor.render(&stack, &mut last_rendered);
+
+        assert_eq!(calls.lock().unwrap().remove(0), vec![(0, 0, (0, 0, 0))]);
+    }
+}
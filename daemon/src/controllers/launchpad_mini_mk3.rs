@@ -1,23 +1,25 @@
 use std::{
     sync::{Arc, Mutex},
+    thread,
     time::Duration,
 };
 
-use crate::scripts::Script;
-
-use super::{Alles, Controller, ControllerEvent, ScriptRunner};
+use super::{output_throttle::OutputThrottle, Controller, ControllerEvent, RgbColor};
 use launchy::{
-    launchpad_mini_mk3::PaletteColor, InputDevice, InputDeviceHandlerPolling, MidiError,
-    MsgPollingWrapper, OutputDevice,
+    InputDevice, InputDeviceHandlerPolling, MidiError, MsgPollingWrapper, OutputDevice,
 };
-use tokio::sync::broadcast::error::TryRecvError;
 use tracing::info;
 
+/// How long rapid `set_button_color` calls to the same button are allowed to
+/// coalesce before the batch is flushed to the device.
+const OUTPUT_DEBOUNCE: Duration = Duration::from_millis(8);
+
 pub struct LaunchpadMiniMk3 {
     midi_in: Arc<Mutex<InputDeviceHandlerPolling<launchy::mini_mk3::Message>>>,
     midi_out: Arc<Mutex<launchy::mini_mk3::Output>>,
     event_sender: Arc<Mutex<tokio::sync::broadcast::Sender<ControllerEvent>>>,
     event_receiver: tokio::sync::broadcast::Receiver<ControllerEvent>,
+    throttle: Arc<OutputThrottle>,
 }
 
 #[async_trait::async_trait]
@@ -27,19 +29,12 @@ impl Controller for LaunchpadMiniMk3 {
         let midi_out = Arc::new(Mutex::new(launchy::mini_mk3::Output::guess()?));
         let (event_sender, event_receiver) = tokio::sync::broadcast::channel(10);
 
-        // Mock receiver magically works lmao
-        // tokio::spawn(async move {
-        //     loop {
-        //         let message = event_receiver.recv().await.unwrap();
-        //         info!("Idle Received message: {:?}", message);
-        //     }
-        // });
-
         Ok(Box::new(Self {
             midi_in,
             midi_out,
             event_receiver,
             event_sender: Arc::new(Mutex::new(event_sender)),
+            throttle: OutputThrottle::new(OUTPUT_DEBOUNCE),
         }))
     }
 
@@ -56,18 +51,31 @@ impl Controller for LaunchpadMiniMk3 {
         let sender = self.event_sender.clone();
         let midi_in = self.midi_in.clone();
 
-        tokio::spawn(async move {
-            info!("Starting midi_in loop");
+        // `InputDeviceHandlerPolling` only offers a blocking `recv_timeout`, so
+        // it gets its own OS thread that forwards decoded messages into a
+        // channel. The async side just awaits on that channel instead of
+        // busy-polling with a sleep.
+        let (message_tx, mut message_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        thread::spawn(move || {
+            info!("Starting midi_in thread");
 
             let midi_in = midi_in.lock().unwrap();
 
-            while let message = midi_in.recv_timeout(Duration::from_millis(10)) {
-                let Some(message) = message else {
-                    // tokio::time::sleep(std::time::Duration::from_millis(10)).await;
-                    // info!("Midi -> timeout");
-                    continue;
-                };
+            loop {
+                match midi_in.recv_timeout(Duration::from_millis(10)) {
+                    Some(message) => {
+                        if message_tx.send(message).is_err() {
+                            break;
+                        }
+                    }
+                    None => continue,
+                }
+            }
+        });
 
+        tokio::spawn(async move {
+            while let Some(message) = message_rx.recv().await {
                 info!("MIDI OPERATION");
 
                 let sender = sender.lock().unwrap();
@@ -118,6 +126,33 @@ impl Controller for LaunchpadMiniMk3 {
             }
         });
 
+        let midi_out = self.midi_out.clone();
+        let throttle = self.throttle.clone();
+
+        tokio::spawn(async move {
+            throttle
+                .run(move |batch| {
+                    let mut midi_out = midi_out.lock().unwrap();
+
+                    // Each button in the batch is written as its own RGB
+                    // message; the batch is not combined into one SysEx write.
+                    for &(x, y, (r, g, b)) in batch {
+                        let button = if y == 0 {
+                            launchy::mini_mk3::Button::ControlButton { index: x }
+                        } else {
+                            launchy::mini_mk3::Button::GridButton { x, y: y - 1 }
+                        };
+
+                        if let Err(error) =
+                            midi_out.light_rgb(button, launchy::RgbColor::new(r, g, b))
+                        {
+                            info!("Error writing batched button color: {}", error);
+                        }
+                    }
+                })
+                .await;
+        });
+
         Ok(())
     }
 
@@ -135,14 +170,6 @@ impl Controller for LaunchpadMiniMk3 {
     fn get_event_receiver(&self) -> Result<tokio::sync::broadcast::Receiver<ControllerEvent>, ()> {
         info!("Getting event receiver");
 
-        // let event_sender = self.event_sender.clone();
-        // tokio::spawn(async move {
-        //     // wait 2 seconds
-        //     tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-
-        //     event_sender.send(ControllerEvent::Heartbeat).unwrap();
-        // });
-
         Ok(self.event_receiver.resubscribe())
     }
 
@@ -150,9 +177,7 @@ impl Controller for LaunchpadMiniMk3 {
         "Launchpad Mini Mk3"
     }
 
-    fn set_button_color_multi(&self, updates: &[(u8, u8, u8)]) -> Result<(), MidiError> {
-        let mut midi_out: std::sync::MutexGuard<'_, launchy::launchpad_mini_mk3::Output> =
-            self.midi_out.lock().unwrap();
+    fn set_button_color_multi(&self, updates: &[(u8, u8, RgbColor)]) -> Result<(), MidiError> {
         let sender = self.event_sender.lock().unwrap();
         sender
             .send(ControllerEvent::LightUpdate {
@@ -161,87 +186,12 @@ impl Controller for LaunchpadMiniMk3 {
             .unwrap();
         drop(sender);
 
-        for (x, y, color) in updates {
-            let color = match color {
-                0 => PaletteColor::BLACK,
-                // 1 => PaletteColor::DARK_GRAY,
-                // 2 => PaletteColor::LIGHT_GRAY,
-                1 => PaletteColor::WHITE,
-                2 => PaletteColor::RED,
-                3 => PaletteColor::YELLOW,
-                4 => PaletteColor::BLUE,
-                5 => PaletteColor::MAGENTA,
-                6 => PaletteColor::BROWN,
-                7 => PaletteColor::CYAN,
-                8 => PaletteColor::GREEN,
-                _ => PaletteColor::BLACK,
-            };
-
-            let button = if *y == 0 {
-                launchy::mini_mk3::Button::ControlButton { index: *x }
-            } else {
-                launchy::mini_mk3::Button::GridButton { x: *x, y: y - 1 }
-            };
-
-            midi_out.light(button, color)?;
-            // midi_out.light(
-            //     launchy::mini_mk3::Button::GridButton { x: *x, y: *y },
-            //     color,
-            // )?;
-        }
+        self.throttle.push(updates);
 
         Ok(())
     }
 
-    fn set_button_color(&self, x: u8, y: u8, color: u8) -> Result<(), MidiError> {
+    fn set_button_color(&self, x: u8, y: u8, color: RgbColor) -> Result<(), MidiError> {
         self.set_button_color_multi(&vec![(x, y, color)])
     }
 }
-
-#[async_trait::async_trait]
-impl ScriptRunner for LaunchpadMiniMk3 {
-    async fn run(&self, script: &mut dyn Script) -> Result<(), MidiError> {
-        script.initialize(self);
-
-        let mut receiver = self.get_event_receiver().unwrap();
-
-        loop {
-            match receiver.try_recv() {
-                Ok(message) => match message {
-                    ControllerEvent::Press { x, y } => {
-                        info!("Received press event: {} {}", x, y);
-                        script.on_press(x, y, self);
-                    }
-                    ControllerEvent::Release { x, y } => {
-                        info!("Received release event: {} {}", x, y);
-                        script.on_release(x, y, self);
-                    }
-                    _ => {
-                        info!("Received message: {:?}", message)
-                    }
-                },
-                Err(error) => match error {
-                    TryRecvError::Empty => {
-                        // info!("Empty");
-                        // break;
-                    }
-                    TryRecvError::Closed => {
-                        info!("Closed");
-                        break;
-                    }
-                    TryRecvError::Lagged(_) => {
-                        info!("Lagged");
-
-                        return self.run(script).await;
-                    }
-                },
-            }
-
-            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
-        }
-
-        Ok(())
-    }
-}
-
-impl Alles for LaunchpadMiniMk3 {}
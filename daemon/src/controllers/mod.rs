@@ -0,0 +1,63 @@
+use launchy::MidiError;
+use serde::{Deserialize, Serialize};
+
+pub mod animation;
+pub mod compositor;
+pub mod launchpad_mini_mk3;
+pub mod output_throttle;
+
+pub use launchpad_mini_mk3::LaunchpadMiniMk3;
+
+/// A full-range RGB color, as sent over SysEx to controllers that support it.
+pub type RgbColor = (u8, u8, u8);
+
+/// The nine-entry palette older calls used to address with a single index.
+/// Kept around as a convenience for callers that don't need full RGB.
+pub fn palette_to_rgb(index: u8) -> RgbColor {
+    match index {
+        1 => (255, 255, 255), // WHITE
+        2 => (255, 0, 0),     // RED
+        3 => (255, 255, 0),   // YELLOW
+        4 => (0, 0, 255),     // BLUE
+        5 => (255, 0, 255),   // MAGENTA
+        6 => (139, 69, 19),   // BROWN
+        7 => (0, 255, 255),   // CYAN
+        8 => (0, 255, 0),     // GREEN
+        _ => (0, 0, 0),       // BLACK
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ControllerEvent {
+    Press { x: u8, y: u8 },
+    Release { x: u8, y: u8 },
+    LightUpdate { updates: Vec<(u8, u8, RgbColor)> },
+    ClearBoard,
+}
+
+pub trait Controller: Send + Sync {
+    fn guess() -> Result<Box<Self>, MidiError>
+    where
+        Self: Sized;
+
+    fn guess_ok() -> Result<(), MidiError>
+    where
+        Self: Sized;
+
+    fn initialize(&self) -> Result<(), MidiError>;
+
+    fn clear(&self) -> Result<(), MidiError>;
+
+    fn get_event_receiver(&self) -> Result<tokio::sync::broadcast::Receiver<ControllerEvent>, ()>;
+
+    fn name(&self) -> &str;
+
+    fn set_button_color(&self, x: u8, y: u8, color: RgbColor) -> Result<(), MidiError>;
+
+    fn set_button_color_multi(&self, updates: &[(u8, u8, RgbColor)]) -> Result<(), MidiError>;
+
+    /// Convenience wrapper for callers still thinking in the old nine-color palette.
+    fn set_button_palette_color(&self, x: u8, y: u8, index: u8) -> Result<(), MidiError> {
+        self.set_button_color(x, y, palette_to_rgb(index))
+    }
+}
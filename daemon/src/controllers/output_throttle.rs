@@ -0,0 +1,99 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::sync::Notify;
+
+use super::RgbColor;
+
+/// Coalesces rapid button writes: repeated writes to the same button within
+/// `debounce` of each other collapse to just the last one, and everything
+/// queued up is handed to `flush` together as a single batch. Deduplication
+/// is the only reduction: the batch holds one entry per distinct button.
+pub struct OutputThrottle {
+    pending: Mutex<HashMap<(u8, u8), RgbColor>>,
+    notify: Notify,
+    debounce: Duration,
+}
+
+impl OutputThrottle {
+    pub fn new(debounce: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            pending: Mutex::new(HashMap::new()),
+            notify: Notify::new(),
+            debounce,
+        })
+    }
+
+    /// Queues updates for the next flush, overwriting any write already
+    /// pending for the same button.
+    pub fn push(&self, updates: &[(u8, u8, RgbColor)]) {
+        let mut pending = self.pending.lock().unwrap();
+        for &(x, y, color) in updates {
+            pending.insert((x, y), color);
+        }
+        drop(pending);
+
+        self.notify.notify_one();
+    }
+
+    /// Runs forever, waiting for queued writes and handing the debounced,
+    /// deduplicated batch to `flush` once per window.
+    pub async fn run(self: Arc<Self>, mut flush: impl FnMut(&[(u8, u8, RgbColor)])) {
+        loop {
+            self.notify.notified().await;
+            tokio::time::sleep(self.debounce).await;
+
+            let batch: Vec<(u8, u8, RgbColor)> = {
+                let mut pending = self.pending.lock().unwrap();
+                pending
+                    .drain()
+                    .map(|((x, y), color)| (x, y, color))
+                    .collect()
+            };
+
+            if !batch.is_empty() {
+                flush(&batch);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_collapses_repeated_writes_to_the_same_button() {
+        let throttle = OutputThrottle::new(Duration::from_millis(10));
+        throttle.push(&[(0, 0, (255, 0, 0))]);
+        throttle.push(&[(0, 0, (0, 255, 0)), (1, 1, (0, 0, 255))]);
+
+        let pending = throttle.pending.lock().unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending.get(&(0, 0)), Some(&(0, 255, 0)));
+        assert_eq!(pending.get(&(1, 1)), Some(&(0, 0, 255)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn run_flushes_once_with_only_the_latest_write_per_button() {
+        let throttle = OutputThrottle::new(Duration::from_millis(10));
+        let flushed = Arc::new(Mutex::new(Vec::new()));
+
+        let flushed_for_run = flushed.clone();
+        let handle = tokio::spawn(throttle.clone().run(move |batch| {
+            flushed_for_run.lock().unwrap().push(batch.to_vec());
+        }));
+
+        throttle.push(&[(0, 0, (1, 2, 3))]);
+        tokio::time::advance(Duration::from_millis(5)).await;
+        throttle.push(&[(0, 0, (4, 5, 6))]);
+        tokio::time::advance(Duration::from_millis(20)).await;
+
+        assert_eq!(flushed.lock().unwrap().as_slice(), &[vec![(0, 0, (4, 5, 6))]]);
+
+        handle.abort();
+    }
+}
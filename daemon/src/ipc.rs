@@ -0,0 +1,173 @@
+use std::{path::PathBuf, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{UnixListener, UnixStream},
+    sync::mpsc,
+};
+use tracing::{error, info};
+
+use crate::{controllers::ControllerEvent, state::AppState};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ClientMessage {
+    ListControllers,
+    SubscribeEvents { controller: String },
+    SetButton {
+        controller: String,
+        x: u8,
+        y: u8,
+        rgb: (u8, u8, u8),
+    },
+    RunScript { controller: String, script: String },
+    ClearBoard { controller: String },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ServerMessage {
+    ControllerList(Vec<String>),
+    Event(ControllerEvent),
+    Ack,
+    Error(String),
+}
+
+fn socket_path() -> PathBuf {
+    let runtime_dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".to_string());
+    PathBuf::from(runtime_dir).join("launchpi.sock")
+}
+
+/// Serves the Unix-socket control protocol alongside the HTTP API, so other
+/// processes can drive the Launchpad and observe presses without polling HTTP.
+pub async fn serve(state: Arc<AppState>) -> std::io::Result<()> {
+    let path = socket_path();
+    let _ = std::fs::remove_file(&path);
+
+    let listener = UnixListener::bind(&path)?;
+    info!("IPC socket listening on {}", path.display());
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let state = state.clone();
+
+        tokio::spawn(async move {
+            if let Err(error) = handle_client(stream, state).await {
+                error!("IPC client error: {}", error);
+            }
+        });
+    }
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    message: &ServerMessage,
+) -> std::io::Result<()> {
+    let payload = serde_json::to_vec(message)?;
+    writer.write_u32(payload.len() as u32).await?;
+    writer.write_all(&payload).await?;
+    Ok(())
+}
+
+async fn read_message<R: AsyncRead + Unpin>(
+    reader: &mut R,
+) -> std::io::Result<Option<ClientMessage>> {
+    let len = match reader.read_u32().await {
+        Ok(len) => len,
+        Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    let mut buffer = vec![0u8; len as usize];
+    reader.read_exact(&mut buffer).await?;
+    Ok(Some(serde_json::from_slice(&buffer)?))
+}
+
+async fn handle_client(stream: UnixStream, state: Arc<AppState>) -> std::io::Result<()> {
+    let (mut read_half, mut write_half) = stream.into_split();
+    let (out_tx, mut out_rx) = mpsc::channel::<ServerMessage>(32);
+
+    let writer = tokio::spawn(async move {
+        while let Some(message) = out_rx.recv().await {
+            if write_message(&mut write_half, &message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    while let Some(message) = read_message(&mut read_half).await? {
+        match message {
+            ClientMessage::ListControllers => {
+                let _ = out_tx
+                    .send(ServerMessage::ControllerList(state.controller_names()))
+                    .await;
+            }
+            ClientMessage::SubscribeEvents { controller } => {
+                match state.find_controller(&controller) {
+                    Some(controller) => match controller.get_event_receiver() {
+                        Ok(mut receiver) => {
+                            let out_tx = out_tx.clone();
+                            tokio::spawn(async move {
+                                while let Ok(event) = receiver.recv().await {
+                                    if out_tx.send(ServerMessage::Event(event)).await.is_err() {
+                                        break;
+                                    }
+                                }
+                            });
+                        }
+                        Err(()) => {
+                            let _ = out_tx
+                                .send(ServerMessage::Error("No event receiver".to_string()))
+                                .await;
+                        }
+                    },
+                    None => {
+                        let _ = out_tx
+                            .send(ServerMessage::Error(format!(
+                                "Unknown controller: {controller}"
+                            )))
+                            .await;
+                    }
+                }
+            }
+            ClientMessage::SetButton {
+                controller,
+                x,
+                y,
+                rgb,
+            } => {
+                let response = match state.find_controller(&controller) {
+                    Some(controller) => match controller.set_button_color(x, y, rgb) {
+                        Ok(()) => ServerMessage::Ack,
+                        Err(error) => ServerMessage::Error(error.to_string()),
+                    },
+                    None => ServerMessage::Error(format!("Unknown controller: {controller}")),
+                };
+                let _ = out_tx.send(response).await;
+            }
+            ClientMessage::ClearBoard { controller } => {
+                let response = match state.find_controller(&controller) {
+                    Some(controller) => match controller.clear() {
+                        Ok(()) => ServerMessage::Ack,
+                        Err(error) => ServerMessage::Error(error.to_string()),
+                    },
+                    None => ServerMessage::Error(format!("Unknown controller: {controller}")),
+                };
+                let _ = out_tx.send(response).await;
+            }
+            ClientMessage::RunScript { controller, script } => {
+                // Module instances are bound to a button at config time, not
+                // spawned ad hoc by name, so there's nothing to dispatch this
+                // into yet. Tell the client honestly rather than ack a no-op.
+                info!("RunScript requested: {} on {}", script, controller);
+                let _ = out_tx
+                    .send(ServerMessage::Error("not implemented".to_string()))
+                    .await;
+            }
+        }
+    }
+
+    drop(out_tx);
+    let _ = writer.await;
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use std::{
+    path::PathBuf,
     process,
     sync::{Arc, Mutex},
     thread,
@@ -6,11 +7,15 @@ use std::{
 };
 
 use tokio::select;
-use tracing::info;
+use tracing::{info, warn};
+
+use controllers::ControllerEvent;
 
 mod api;
 mod controllers;
-mod scripts;
+mod ipc;
+mod modules;
+mod mpris;
 mod sound;
 mod state;
 
@@ -24,52 +29,89 @@ async fn main() {
     let controllers: Arc<Mutex<Vec<Arc<Box<dyn controllers::Controller>>>>> =
         Arc::new(Mutex::new(Vec::new()));
 
-    let state = Arc::new(state::AppState {
-        controller_tx,
-        controllers,
-    });
+    let state = Arc::new(state::AppState::new(controller_tx, controllers));
+
+    let module_host = Arc::new(modules::ModuleHost::new(config_path(), state.clone()));
+    if let Err(error) = module_host.start().await {
+        warn!("Failed to load module config: {}", error);
+    }
 
     let state1 = state.clone();
+    let module_host1 = module_host.clone();
     tokio::spawn(async move {
         while let Some(controller) = controller_rx.recv().await {
             info!("Received controller");
             controller.initialize().unwrap();
 
+            subscribe_to_presses(controller.clone(), module_host1.clone());
             state1.controllers.lock().unwrap().push(controller.clone());
         }
     });
 
-    // let mut controllers: Vec<Arc<Box<dyn Alles>>> = Vec::new();
-
-    // let controller: Arc<Box<dyn Alles>> = Arc::new(LaunchpadMiniMk1::guess().unwrap());
-    // controllers.push(controller.clone());
-    // let controller2: Arc<Box<dyn Alles>> = Arc::new(LaunchpadMiniMk3::guess().unwrap());
-    // controllers.push(controller2.clone());
-
-    // controller.initialize().unwrap();
-    // controller2.initialize().unwrap();
-
-    // let mut script = scripts::ping::PingScript::new();
-
-    // let controller1 = controller.clone();
-    // tokio::spawn(async move { controller1.run(&mut script).unwrap() });
-
-    // let mut script2 = scripts::soundboard::SoundboardScript::new();
+    let reload_host = module_host.clone();
+    tokio::spawn(async move {
+        loop {
+            if signal_hangup().await.is_some() {
+                info!("Reloading module config");
+                if let Err(error) = reload_host.reload().await {
+                    warn!("Failed to reload module config: {}", error);
+                }
+            }
+        }
+    });
 
-    // let controller21 = controller2.clone();
-    // tokio::spawn(async move { controller21.run(&mut script2).unwrap() });
+    let ipc_state = state.clone();
 
     select! {
         _ = api::serve(state) => {},
+        result = ipc::serve(ipc_state) => {
+            if let Err(error) = result {
+                info!("IPC server stopped: {}", error);
+            }
+        },
         _ = tokio::signal::ctrl_c() => {
             info!("Received SIGINT, shutting down");
         },
     }
 
-    // controller.clear().unwrap();
-    // controller2.clear().unwrap();
-
     thread::sleep(Duration::from_millis(100));
 
     process::exit(0);
 }
+
+fn config_path() -> PathBuf {
+    std::env::var("LAUNCHPI_CONFIG")
+        .unwrap_or_else(|_| "launchpi.toml".to_string())
+        .into()
+}
+
+/// Forwards presses and releases on a controller into the module host so
+/// whichever module is mapped to that button gets notified.
+fn subscribe_to_presses(
+    controller: Arc<Box<dyn controllers::Controller>>,
+    module_host: Arc<modules::ModuleHost>,
+) {
+    tokio::spawn(async move {
+        let Ok(mut receiver) = controller.get_event_receiver() else {
+            return;
+        };
+
+        while let Ok(event) = receiver.recv().await {
+            match event {
+                ControllerEvent::Press { x, y } => {
+                    module_host.dispatch(controller.name(), x, y, true).await;
+                }
+                ControllerEvent::Release { x, y } => {
+                    module_host.dispatch(controller.name(), x, y, false).await;
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
+/// Waits for SIGHUP, the conventional "reload your config" signal.
+async fn signal_hangup() -> Option<()> {
+    let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok()?;
+    hangup.recv().await
+}
@@ -0,0 +1,389 @@
+use std::{process::Command, time::Duration};
+
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use super::{ButtonStateUpdate, HostEvent, Module};
+use crate::controllers::{
+    animation::{AnimationScheduler, Pulse as PulseAnimation},
+    RgbColor,
+};
+
+const IDLE: RgbColor = (20, 20, 20);
+const ACTIVE: RgbColor = (0, 255, 0);
+const OFF: RgbColor = (0, 0, 0);
+const PAUSED: RgbColor = (0, 40, 0);
+const DEFAULT_PULSE_PERIOD: Duration = Duration::from_secs(2);
+const ANIMATION_TICK: Duration = Duration::from_millis(33);
+const MPRIS_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Runs a shell command every time its button is pressed.
+pub struct RunCommand {
+    command: String,
+}
+
+#[async_trait::async_trait]
+impl Module for RunCommand {
+    async fn run(
+        &mut self,
+        mut events: mpsc::Receiver<HostEvent>,
+        render_tx: mpsc::Sender<ButtonStateUpdate>,
+    ) {
+        let _ = render_tx.send(ButtonStateUpdate::SetColor(IDLE)).await;
+
+        while let Some(event) = events.recv().await {
+            if !matches!(event, HostEvent::ButtonPressed) {
+                continue;
+            }
+
+            let _ = render_tx.send(ButtonStateUpdate::SetColor(ACTIVE)).await;
+
+            if let Err(error) = Command::new("sh").arg("-c").arg(&self.command).spawn() {
+                warn!("Failed to launch command '{}': {}", self.command, error);
+            }
+
+            let _ = render_tx.send(ButtonStateUpdate::SetColor(IDLE)).await;
+        }
+    }
+}
+
+/// Plays a sound file every time its button is pressed.
+pub struct PlaySound {
+    path: String,
+}
+
+#[async_trait::async_trait]
+impl Module for PlaySound {
+    async fn run(
+        &mut self,
+        mut events: mpsc::Receiver<HostEvent>,
+        render_tx: mpsc::Sender<ButtonStateUpdate>,
+    ) {
+        let _ = render_tx.send(ButtonStateUpdate::SetColor(IDLE)).await;
+
+        while let Some(event) = events.recv().await {
+            if !matches!(event, HostEvent::ButtonPressed) {
+                continue;
+            }
+
+            let path = self.path.clone();
+            if let Err(error) =
+                tokio::task::spawn_blocking(move || crate::sound::play(&path)).await
+            {
+                warn!("Sound playback task panicked: {}", error);
+            }
+        }
+    }
+}
+
+/// Flips between two colors on each press; useful for mute/enable switches.
+pub struct Toggle {
+    on_color: RgbColor,
+    off_color: RgbColor,
+    on: bool,
+}
+
+#[async_trait::async_trait]
+impl Module for Toggle {
+    async fn run(
+        &mut self,
+        mut events: mpsc::Receiver<HostEvent>,
+        render_tx: mpsc::Sender<ButtonStateUpdate>,
+    ) {
+        let color = if self.on { self.on_color } else { self.off_color };
+        let _ = render_tx.send(ButtonStateUpdate::SetColor(color)).await;
+
+        while let Some(event) = events.recv().await {
+            if !matches!(event, HostEvent::ButtonPressed) {
+                continue;
+            }
+
+            self.on = !self.on;
+            let color = if self.on { self.on_color } else { self.off_color };
+            let _ = render_tx.send(ButtonStateUpdate::SetColor(color)).await;
+        }
+    }
+}
+
+/// Ambient indicator that breathes its button's color in and out forever,
+/// driven by the animation engine's `Pulse` effect. Ignores presses.
+pub struct Pulse {
+    color: RgbColor,
+    period: Duration,
+}
+
+#[async_trait::async_trait]
+impl Module for Pulse {
+    async fn run(
+        &mut self,
+        mut events: mpsc::Receiver<HostEvent>,
+        render_tx: mpsc::Sender<ButtonStateUpdate>,
+    ) {
+        let mut scheduler = AnimationScheduler::new(ANIMATION_TICK);
+        scheduler.register(Box::new(PulseAnimation {
+            x: 0,
+            y: 0,
+            base: self.color,
+            period: self.period,
+        }));
+
+        let animate = scheduler.run(|updates| {
+            if let Some(&(_, _, color)) = updates.first() {
+                let _ = render_tx.try_send(ButtonStateUpdate::SetColor(color));
+            }
+        });
+
+        tokio::select! {
+            _ = animate => {}
+            _ = async { while events.recv().await.is_some() {} } => {}
+        }
+    }
+}
+
+/// Drives one MPRIS action on press: `play-pause`, `next`, `previous`,
+/// `volume-up` or `volume-down`. `play-pause` additionally mirrors live
+/// playback state onto its own button (green while playing, dim while
+/// paused, off with no player running) by watching `PropertiesChanged` in
+/// the background for as long as the module runs — the other actions just
+/// flash `ACTIVE` to acknowledge the press.
+pub struct MediaControl {
+    action: String,
+}
+
+#[async_trait::async_trait]
+impl Module for MediaControl {
+    async fn run(
+        &mut self,
+        mut events: mpsc::Receiver<HostEvent>,
+        render_tx: mpsc::Sender<ButtonStateUpdate>,
+    ) {
+        let _ = render_tx.send(ButtonStateUpdate::SetColor(IDLE)).await;
+
+        let mirrors_playback = self.action == "play-pause";
+        let presses = async {
+            while let Some(event) = events.recv().await {
+                if !matches!(event, HostEvent::ButtonPressed) {
+                    continue;
+                }
+
+                if let Err(error) = self.trigger().await {
+                    warn!("MPRIS action '{}' failed: {}", self.action, error);
+                }
+
+                if !mirrors_playback {
+                    let _ = render_tx.send(ButtonStateUpdate::SetColor(ACTIVE)).await;
+                    let _ = render_tx.send(ButtonStateUpdate::SetColor(IDLE)).await;
+                }
+            }
+        };
+
+        if mirrors_playback {
+            let watch = Self::watch_playback(render_tx.clone());
+            tokio::pin!(watch);
+            tokio::pin!(presses);
+            tokio::select! {
+                _ = &mut watch => {}
+                _ = &mut presses => {}
+            }
+        } else {
+            presses.await;
+        }
+    }
+}
+
+impl MediaControl {
+    async fn trigger(&self) -> zbus::Result<()> {
+        match self.action.as_str() {
+            "volume-up" => Self::adjust_volume(0.1).await,
+            "volume-down" => Self::adjust_volume(-0.1).await,
+            action => {
+                let connection = zbus::Connection::session().await?;
+                let Some(destination) = crate::mpris::active_player(&connection).await? else {
+                    return Ok(());
+                };
+                let proxy = crate::mpris::player_proxy(&connection, &destination).await?;
+                proxy.call_method(method_for(action), &()).await?;
+                Ok(())
+            }
+        }
+    }
+
+    async fn adjust_volume(delta: f64) -> zbus::Result<()> {
+        let connection = zbus::Connection::session().await?;
+        let Some(destination) = crate::mpris::active_player(&connection).await? else {
+            return Ok(());
+        };
+        let proxy = crate::mpris::player_proxy(&connection, &destination).await?;
+        let current: f64 = proxy.get_property("Volume").await.unwrap_or(0.0);
+        proxy
+            .set_property("Volume", (current + delta).clamp(0.0, 1.0))
+            .await?;
+        Ok(())
+    }
+
+    /// Loops forever, re-watching for a player to appear whenever the
+    /// current one's signal stream ends (it quit, or none was running yet).
+    async fn watch_playback(render_tx: mpsc::Sender<ButtonStateUpdate>) {
+        loop {
+            let Ok(connection) = zbus::Connection::session().await else {
+                tokio::time::sleep(MPRIS_RETRY_DELAY).await;
+                continue;
+            };
+
+            crate::mpris::watch_player(&connection, || async {
+                let color = Self::playback_color(&connection).await;
+                let _ = render_tx.send(ButtonStateUpdate::SetColor(color)).await;
+            })
+            .await;
+
+            tokio::time::sleep(MPRIS_RETRY_DELAY).await;
+        }
+    }
+
+    async fn playback_color(connection: &zbus::Connection) -> RgbColor {
+        let Ok(Some(destination)) = crate::mpris::active_player(connection).await else {
+            return OFF;
+        };
+        let Ok(proxy) = crate::mpris::player_proxy(connection, &destination).await else {
+            return OFF;
+        };
+
+        let status: String = proxy
+            .get_property("PlaybackStatus")
+            .await
+            .unwrap_or_else(|_| "Stopped".to_string());
+        if status == "Playing" {
+            ACTIVE
+        } else {
+            PAUSED
+        }
+    }
+}
+
+fn method_for(action: &str) -> &str {
+    match action {
+        "next" => "Next",
+        "previous" => "Previous",
+        _ => "PlayPause",
+    }
+}
+
+/// One segment of a volume meter: lights up once the current MPRIS volume
+/// covers its `index` out of `count` segments, watching `PropertiesChanged`
+/// the same way `MediaControl`'s `play-pause` action does.
+pub struct VolumeMeter {
+    index: usize,
+    count: usize,
+    color: RgbColor,
+}
+
+#[async_trait::async_trait]
+impl Module for VolumeMeter {
+    async fn run(
+        &mut self,
+        mut events: mpsc::Receiver<HostEvent>,
+        render_tx: mpsc::Sender<ButtonStateUpdate>,
+    ) {
+        let _ = render_tx.send(ButtonStateUpdate::SetColor(OFF)).await;
+
+        let index = self.index;
+        let count = self.count;
+        let color = self.color;
+
+        let watch = async {
+            loop {
+                let Ok(connection) = zbus::Connection::session().await else {
+                    tokio::time::sleep(MPRIS_RETRY_DELAY).await;
+                    continue;
+                };
+
+                crate::mpris::watch_player(&connection, || async {
+                    let segment = Self::segment_color(&connection, index, count, color).await;
+                    let _ = render_tx.send(ButtonStateUpdate::SetColor(segment)).await;
+                })
+                .await;
+
+                tokio::time::sleep(MPRIS_RETRY_DELAY).await;
+            }
+        };
+
+        tokio::select! {
+            _ = watch => {}
+            _ = async { while events.recv().await.is_some() {} } => {}
+        }
+    }
+}
+
+impl VolumeMeter {
+    async fn segment_color(
+        connection: &zbus::Connection,
+        index: usize,
+        count: usize,
+        color: RgbColor,
+    ) -> RgbColor {
+        let Ok(Some(destination)) = crate::mpris::active_player(connection).await else {
+            return OFF;
+        };
+        let Ok(proxy) = crate::mpris::player_proxy(connection, &destination).await else {
+            return OFF;
+        };
+
+        let volume: f64 = proxy.get_property("Volume").await.unwrap_or(0.0);
+        let lit = (volume.clamp(0.0, 1.0) * count as f64).round() as usize;
+        if index < lit {
+            color
+        } else {
+            OFF
+        }
+    }
+}
+
+fn parse_color(value: &toml::Value) -> Option<RgbColor> {
+    let array = value.as_array()?;
+    let [r, g, b] = <[_; 3]>::try_from(array.as_slice()).ok()?;
+    Some((
+        r.as_integer()? as u8,
+        g.as_integer()? as u8,
+        b.as_integer()? as u8,
+    ))
+}
+
+/// Instantiates a built-in module from its config name and TOML params.
+pub fn create(name: &str, params: &toml::Value) -> Option<Box<dyn Module>> {
+    match name {
+        "run-command" => Some(Box::new(RunCommand {
+            command: params.get("command")?.as_str()?.to_string(),
+        })),
+        "play-sound" => Some(Box::new(PlaySound {
+            path: params.get("path")?.as_str()?.to_string(),
+        })),
+        "toggle" => Some(Box::new(Toggle {
+            on_color: params
+                .get("on_color")
+                .and_then(parse_color)
+                .unwrap_or(ACTIVE),
+            off_color: params
+                .get("off_color")
+                .and_then(parse_color)
+                .unwrap_or(IDLE),
+            on: false,
+        })),
+        "media-control" => Some(Box::new(MediaControl {
+            action: params.get("action")?.as_str()?.to_string(),
+        })),
+        "volume-meter" => Some(Box::new(VolumeMeter {
+            index: params.get("index")?.as_integer()? as usize,
+            count: params.get("count")?.as_integer()? as usize,
+            color: params.get("color").and_then(parse_color).unwrap_or(ACTIVE),
+        })),
+        "pulse" => Some(Box::new(Pulse {
+            color: params.get("color").and_then(parse_color).unwrap_or(ACTIVE),
+            period: params
+                .get("period_ms")
+                .and_then(|value| value.as_integer())
+                .map(|ms| Duration::from_millis(ms as u64))
+                .unwrap_or(DEFAULT_PULSE_PERIOD),
+        })),
+        _ => None,
+    }
+}
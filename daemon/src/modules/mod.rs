@@ -0,0 +1,279 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::Deserialize;
+use tokio::{
+    sync::{mpsc, Mutex},
+    task::JoinHandle,
+};
+use tracing::{info, warn};
+
+use crate::{
+    controllers::{
+        compositor::{Action, Frame},
+        RgbColor,
+    },
+    state::AppState,
+};
+
+pub mod builtins;
+
+/// Sent from the host to a running module: what happened to its mapped
+/// button, or a request to push a fresh render.
+#[derive(Debug, Clone)]
+pub enum HostEvent {
+    ButtonPressed,
+    ButtonReleased,
+    RequestRedraw,
+}
+
+/// Sent back from a module to the host: the render command to apply to its
+/// mapped button.
+#[derive(Debug, Clone)]
+pub enum ButtonStateUpdate {
+    SetColor(RgbColor),
+}
+
+/// A user-configurable unit of behavior bound to a single button. Runs as its
+/// own task for as long as it's mapped, reacting to `HostEvent`s and pushing
+/// `ButtonStateUpdate`s back.
+#[async_trait::async_trait]
+pub trait Module: Send {
+    async fn run(
+        &mut self,
+        events: mpsc::Receiver<HostEvent>,
+        render_tx: mpsc::Sender<ButtonStateUpdate>,
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ButtonKey {
+    pub controller: String,
+    pub x: u8,
+    pub y: u8,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct ButtonConfig {
+    pub controller: String,
+    pub x: u8,
+    pub y: u8,
+    pub module: String,
+    #[serde(default)]
+    pub params: toml::Value,
+}
+
+impl ButtonConfig {
+    fn key(&self) -> ButtonKey {
+        ButtonKey {
+            controller: self.controller.clone(),
+            x: self.x,
+            y: self.y,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct Config {
+    #[serde(default)]
+    pub buttons: Vec<ButtonConfig>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let raw = std::fs::read_to_string(path)?;
+        toml::from_str(&raw)
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))
+    }
+}
+
+struct RunningModule {
+    config: ButtonConfig,
+    handle: JoinHandle<()>,
+    events_tx: mpsc::Sender<HostEvent>,
+}
+
+/// Loads button-to-module mappings from a TOML config and keeps one task
+/// running per mapped button. `reload` re-reads the config and restarts only
+/// the modules whose mapping or parameters actually changed.
+pub struct ModuleHost {
+    path: PathBuf,
+    state: Arc<AppState>,
+    running: Mutex<HashMap<ButtonKey, RunningModule>>,
+}
+
+impl ModuleHost {
+    pub fn new(path: PathBuf, state: Arc<AppState>) -> Self {
+        Self {
+            path,
+            state,
+            running: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn start(&self) -> std::io::Result<()> {
+        let config = Config::load(&self.path)?;
+        let mut running = self.running.lock().await;
+        for entry in config.buttons {
+            self.spawn(&mut running, entry);
+        }
+        Ok(())
+    }
+
+    /// Re-reads the config file and restarts only modules whose mapping or
+    /// parameters changed, leaving unchanged ones running untouched.
+    pub async fn reload(&self) -> std::io::Result<()> {
+        let config = Config::load(&self.path)?;
+        let mut running = self.running.lock().await;
+
+        let keys: Vec<ButtonKey> = config.buttons.iter().map(ButtonConfig::key).collect();
+        running.retain(|key, _| keys.contains(key));
+
+        for entry in config.buttons {
+            let key = entry.key();
+            if Self::unchanged(&running, &key, &entry) {
+                continue;
+            }
+
+            if let Some(module) = running.remove(&key) {
+                module.handle.abort();
+            }
+
+            self.spawn(&mut running, entry);
+        }
+
+        Ok(())
+    }
+
+    /// Whether `entry` maps to the same mapping and params as whatever is
+    /// already running at its key, i.e. whether `reload` can leave it alone.
+    fn unchanged(
+        running: &HashMap<ButtonKey, RunningModule>,
+        key: &ButtonKey,
+        entry: &ButtonConfig,
+    ) -> bool {
+        running
+            .get(key)
+            .map(|module| &module.config == entry)
+            .unwrap_or(false)
+    }
+
+    /// Forwards a press/release on a physical button to whichever module is
+    /// mapped there, if any.
+    pub async fn dispatch(&self, controller: &str, x: u8, y: u8, pressed: bool) {
+        let key = ButtonKey {
+            controller: controller.to_string(),
+            x,
+            y,
+        };
+
+        let running = self.running.lock().await;
+        if let Some(module) = running.get(&key) {
+            let event = if pressed {
+                HostEvent::ButtonPressed
+            } else {
+                HostEvent::ButtonReleased
+            };
+            let _ = module.events_tx.send(event).await;
+        }
+    }
+
+    fn spawn(&self, running: &mut HashMap<ButtonKey, RunningModule>, entry: ButtonConfig) {
+        let Some(mut module) = builtins::create(&entry.module, &entry.params) else {
+            warn!("Unknown module type: {}", entry.module);
+            return;
+        };
+
+        let key = entry.key();
+        let (events_tx, events_rx) = mpsc::channel(16);
+        let (render_tx, mut render_rx) = mpsc::channel(16);
+
+        let handle = tokio::spawn(async move {
+            module.run(events_rx, render_tx).await;
+        });
+
+        let state = self.state.clone();
+        let render_key = key.clone();
+        // Each mapped button gets its own layer, keyed by its grid position,
+        // so modules share the controller through the compositor instead of
+        // writing buttons directly. Coordinates run 0..=8 on both axes (the
+        // control row and column included), hence the stride of 9.
+        let layer_index = render_key.x as usize * 9 + render_key.y as usize;
+        tokio::spawn(async move {
+            while let Some(ButtonStateUpdate::SetColor(color)) = render_rx.recv().await {
+                if let Some(controller) = state.find_controller(&render_key.controller) {
+                    let mut frame = Frame::new();
+                    frame.insert((render_key.x, render_key.y), color);
+                    let actions = state.compositor_for(&controller);
+                    let _ = actions
+                        .send(Action::ReplaceLayer {
+                            index: layer_index,
+                            frame,
+                        })
+                        .await;
+                }
+            }
+        });
+
+        info!("Started module '{}' at {:?}", entry.module, key);
+        running.insert(
+            key,
+            RunningModule {
+                config: entry,
+                handle,
+                events_tx,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(module: &str) -> ButtonConfig {
+        ButtonConfig {
+            controller: "launchpad".to_string(),
+            x: 0,
+            y: 0,
+            module: module.to_string(),
+            params: toml::Value::default(),
+        }
+    }
+
+    fn running_with(entry: ButtonConfig) -> HashMap<ButtonKey, RunningModule> {
+        let mut running = HashMap::new();
+        let (events_tx, _events_rx) = mpsc::channel(1);
+        running.insert(
+            entry.key(),
+            RunningModule {
+                config: entry,
+                handle: tokio::spawn(async {}),
+                events_tx,
+            },
+        );
+        running
+    }
+
+    #[tokio::test]
+    async fn unchanged_is_true_only_for_an_identical_mapping_at_the_same_key() {
+        let entry = config("toggle");
+        let running = running_with(entry.clone());
+
+        assert!(ModuleHost::unchanged(&running, &entry.key(), &entry));
+
+        let mut different_module = entry.clone();
+        different_module.module = "pulse".to_string();
+        assert!(!ModuleHost::unchanged(&running, &entry.key(), &different_module));
+
+        let missing_key = ButtonKey {
+            controller: "other".to_string(),
+            x: 1,
+            y: 1,
+        };
+        assert!(!ModuleHost::unchanged(&running, &missing_key, &entry));
+    }
+}
@@ -0,0 +1,70 @@
+//! Shared D-Bus lookup helpers for talking to whatever MPRIS-compliant
+//! media player is currently running. Kept separate from any one module so
+//! it has a single place to evolve instead of being copy-pasted per script.
+
+use futures_util::StreamExt;
+
+/// Finds the bus name of the first MPRIS player on the session bus, if any.
+pub async fn active_player(connection: &zbus::Connection) -> zbus::Result<Option<String>> {
+    let dbus = zbus::Proxy::new(
+        connection,
+        "org.freedesktop.DBus",
+        "/org/freedesktop/DBus",
+        "org.freedesktop.DBus",
+    )
+    .await?;
+
+    let names: Vec<String> = dbus.call("ListNames", &()).await?;
+    Ok(names
+        .into_iter()
+        .find(|name| name.starts_with("org.mpris.MediaPlayer2.")))
+}
+
+/// Builds a `Player` proxy for the given MPRIS bus name.
+pub async fn player_proxy<'a>(
+    connection: &'a zbus::Connection,
+    destination: &str,
+) -> zbus::Result<zbus::Proxy<'a>> {
+    zbus::Proxy::new(
+        connection,
+        destination.to_string(),
+        "/org/mpris/MediaPlayer2",
+        "org.mpris.MediaPlayer2.Player",
+    )
+    .await
+}
+
+/// Calls `refresh` once immediately, then again every time the active
+/// player's `PropertiesChanged` signal fires. Returns once there's no active
+/// player to watch or its signal stream ends (player quit); callers that want
+/// to keep watching for the next player to appear should loop on this.
+pub async fn watch_player<F, Fut>(connection: &zbus::Connection, mut refresh: F)
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = ()>,
+{
+    refresh().await;
+
+    let Ok(Some(destination)) = active_player(connection).await else {
+        return;
+    };
+    // `PropertiesChanged` is emitted on the standard properties interface,
+    // not on `org.mpris.MediaPlayer2.Player` itself.
+    let Ok(properties) = zbus::Proxy::new(
+        connection,
+        destination,
+        "/org/mpris/MediaPlayer2",
+        "org.freedesktop.DBus.Properties",
+    )
+    .await
+    else {
+        return;
+    };
+    let Ok(mut changes) = properties.receive_signal("PropertiesChanged").await else {
+        return;
+    };
+
+    while changes.next().await.is_some() {
+        refresh().await;
+    }
+}
@@ -0,0 +1,16 @@
+use std::fs::File;
+use std::io::BufReader;
+
+use rodio::{Decoder, OutputStream, Sink};
+
+/// Plays a sound file to completion on the default output device.
+pub fn play(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let (_stream, stream_handle) = OutputStream::try_default()?;
+    let sink = Sink::try_new(&stream_handle)?;
+
+    let file = BufReader::new(File::open(path)?);
+    sink.append(Decoder::new(file)?);
+    sink.sleep_until_end();
+
+    Ok(())
+}
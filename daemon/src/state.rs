@@ -0,0 +1,63 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::mpsc;
+
+use crate::controllers::{
+    compositor::{Action, Compositor},
+    Controller,
+};
+
+pub struct AppState {
+    pub controller_tx: mpsc::Sender<Arc<Box<dyn Controller>>>,
+    pub controllers: Arc<Mutex<Vec<Arc<Box<dyn Controller>>>>>,
+    compositors: Mutex<HashMap<String, mpsc::Sender<Action>>>,
+}
+
+impl AppState {
+    pub fn new(
+        controller_tx: mpsc::Sender<Arc<Box<dyn Controller>>>,
+        controllers: Arc<Mutex<Vec<Arc<Box<dyn Controller>>>>>,
+    ) -> Self {
+        Self {
+            controller_tx,
+            controllers,
+            compositors: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn controller_names(&self) -> Vec<String> {
+        self.controllers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|controller| controller.name().to_string())
+            .collect()
+    }
+
+    pub fn find_controller(&self, name: &str) -> Option<Arc<Box<dyn Controller>>> {
+        self.controllers
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|controller| controller.name() == name)
+            .cloned()
+    }
+
+    /// Returns the shared compositor action channel for a controller,
+    /// spawning its render task on first use so modules and scripts layer
+    /// over the same grid instead of writing buttons directly.
+    pub fn compositor_for(&self, controller: &Arc<Box<dyn Controller>>) -> mpsc::Sender<Action> {
+        let mut compositors = self.compositors.lock().unwrap();
+        if let Some(sender) = compositors.get(controller.name()) {
+            return sender.clone();
+        }
+
+        let (compositor, sender) = Compositor::new(controller.clone());
+        tokio::spawn(compositor.run());
+        compositors.insert(controller.name().to_string(), sender.clone());
+        sender
+    }
+}